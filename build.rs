@@ -0,0 +1,4 @@
+fn main() {
+    #[cfg(feature = "uniffi")]
+    uniffi_build::generate_scaffolding("src/tdo_core.udl").unwrap();
+}
@@ -0,0 +1,125 @@
+//! UniFFI bindings, exposing `Tdo` to Swift, Kotlin, Python and Ruby.
+//!
+//! `FfiTdo` wraps a `Tdo` behind a `Mutex` since foreign callers hold the
+//! object across calls and may call into it from more than one thread.
+//! Gated behind the `uniffi` feature so plain Rust consumers don't pay for
+//! the extra dependency.
+#![cfg(feature = "uniffi")]
+
+use std::sync::Mutex;
+use tdo::Tdo;
+use todo::Todo;
+use list::TodoList;
+use error::{StorageError, TodoError, TdoError};
+
+uniffi::include_scaffolding!("tdo_core");
+
+/// Error type handed to foreign callers; mirrors `error::TdoError`.
+#[derive(Debug, thiserror::Error)]
+pub enum FfiError {
+    #[error("no such list")]
+    NoSuchList,
+    #[error("no such todo")]
+    NoSuchTodo,
+    #[error("a list with that name already exists")]
+    NameAlreadyExists,
+    #[error("the default list can not be removed")]
+    CanNotRemoveDefault,
+    #[error("file not found")]
+    FileNotFound,
+    #[error("file corrupted")]
+    FileCorrupted,
+    #[error("save failed")]
+    SaveFailure,
+    #[error("unable to convert value")]
+    UnableToConvert,
+    #[error("nothing to undo")]
+    NothingToUndo,
+}
+
+impl From<TdoError> for FfiError {
+    fn from(err: TdoError) -> FfiError {
+        match err {
+            TdoError::Todo(TodoError::NoSuchList) => FfiError::NoSuchList,
+            TdoError::Todo(TodoError::NoSuchTodo) => FfiError::NoSuchTodo,
+            TdoError::Todo(TodoError::NameAlreadyExists) => FfiError::NameAlreadyExists,
+            TdoError::Todo(TodoError::CanNotRemoveDefault) => FfiError::CanNotRemoveDefault,
+            TdoError::Todo(TodoError::NothingToUndo) => FfiError::NothingToUndo,
+            TdoError::Storage(StorageError::FileNotFound) => FfiError::FileNotFound,
+            TdoError::Storage(StorageError::FileCorrupted) => FfiError::FileCorrupted,
+            TdoError::Storage(StorageError::SaveFailure(_)) => FfiError::SaveFailure,
+            TdoError::Storage(StorageError::UnableToConvert(_)) => FfiError::UnableToConvert,
+        }
+    }
+}
+
+pub struct FfiTodo {
+    pub id: u32,
+    pub name: String,
+    pub done: bool,
+}
+
+pub struct FfiTodoList {
+    pub name: String,
+    pub todos: Vec<FfiTodo>,
+}
+
+impl From<&TodoList> for FfiTodoList {
+    fn from(list: &TodoList) -> FfiTodoList {
+        FfiTodoList {
+            name: list.name.clone(),
+            todos: list.todos.iter().map(|t| {
+                FfiTodo { id: t.id, name: t.name.clone(), done: t.done }
+            }).collect(),
+        }
+    }
+}
+
+/// Foreign-facing handle onto a `Tdo` container.
+pub struct FfiTdo {
+    inner: Mutex<Tdo>,
+}
+
+impl FfiTdo {
+    pub fn new() -> FfiTdo {
+        FfiTdo { inner: Mutex::new(Tdo::new()) }
+    }
+
+    pub fn load(path: String) -> Result<FfiTdo, FfiError> {
+        let tdo = Tdo::load(&path)?;
+        Ok(FfiTdo { inner: Mutex::new(tdo) })
+    }
+
+    pub fn save(&self, path: String) -> Result<(), FfiError> {
+        Ok(self.inner.lock().unwrap().save(&path)?)
+    }
+
+    pub fn add_list(&self, name: String) -> Result<(), FfiError> {
+        Ok(self.inner.lock().unwrap().add_list(TodoList::new(&name))?)
+    }
+
+    pub fn remove_list(&self, name: String) -> Result<(), FfiError> {
+        Ok(self.inner.lock().unwrap().remove_list(&name)?)
+    }
+
+    pub fn add_todo(&self, list_name: Option<String>, id: u32, name: String) -> Result<(), FfiError> {
+        let todo = Todo::new(id, &name);
+        Ok(self.inner.lock().unwrap().add_todo(list_name.as_ref().map(String::as_str), todo)?)
+    }
+
+    pub fn done_id(&self, id: u32) {
+        self.inner.lock().unwrap().done_id(id);
+    }
+
+    pub fn remove_id(&self, id: u32) {
+        self.inner.lock().unwrap().remove_id(id);
+    }
+
+    pub fn clean_lists(&self) {
+        self.inner.lock().unwrap().clean_lists();
+    }
+
+    pub fn lists(&self) -> Vec<FfiTodoList> {
+        self.inner.lock().unwrap().lists.iter().map(FfiTodoList::from).collect()
+    }
+}
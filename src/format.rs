@@ -0,0 +1,267 @@
+//! Multi-format import/export for `Tdo` containers.
+//!
+//! Besides its native JSON layout, a `Tdo` can be written to and parsed back
+//! from a handful of plain-text formats so todos can move to and from other
+//! tools.
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use list::TodoList;
+use todo::Todo;
+use tdo::Tdo;
+use error::*;
+
+/// A supported on-disk representation for `export_to`/`import_from`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The native pretty-printed JSON dump.
+    Json,
+    /// One todo per line, list name included as a field, for incremental
+    /// reads of large files.
+    Ndjson,
+    /// `list,id,name,done` rows, for spreadsheet round-tripping.
+    Csv,
+    /// A `##` heading per list with GitHub-style `- [ ]`/`- [x]` items.
+    Markdown,
+}
+
+impl Tdo {
+    /// Export this container to `path`, rendered as `format`.
+    pub fn export_to(&self, path: &str, format: Format) -> TdoResult<()> {
+        if format == Format::Json {
+            return self.save(path);
+        }
+
+        let rendered = match format {
+            Format::Ndjson => render_ndjson(self),
+            Format::Csv => render_csv(self),
+            Format::Markdown => render_markdown(self),
+            Format::Json => unreachable!(),
+        };
+
+        let mut file = File::create(path).map_err(StorageError::SaveFailure)?;
+        file.write_all(rendered.as_bytes()).map_err(StorageError::SaveFailure)?;
+        Ok(())
+    }
+
+    /// Import a container from `path`, parsed as `format`.
+    ///
+    /// NDJSON is read straight off the file's `BufReader`, one line at a
+    /// time, so large dumps never need to sit fully in memory as text; CSV
+    /// and Markdown are read into memory first since rows further down can
+    /// belong to a list opened earlier in the file.
+    ///
+    /// Every CSV and NDJSON row must carry all four fields (`list`, `id`,
+    /// `name`, `done`); partial rows are not accepted. A row that is missing
+    /// a field or can't be turned into a `Todo` yields
+    /// `StorageError::UnableToConvert` naming the offending line.
+    pub fn import_from(path: &str, format: Format) -> TdoResult<Tdo> {
+        if format == Format::Json {
+            return Tdo::load(path);
+        }
+
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(_) => return Err(StorageError::FileNotFound.into()),
+        };
+
+        let lists = match format {
+            Format::Ndjson => parse_ndjson(BufReader::new(file))?,
+            Format::Csv => parse_csv(&read_lines(BufReader::new(file))?)?,
+            Format::Markdown => parse_markdown(&read_lines(BufReader::new(file))?),
+            Format::Json => unreachable!(),
+        };
+
+        Ok(Tdo::from_lists(lists))
+    }
+}
+
+fn read_lines(reader: BufReader<File>) -> TdoResult<Vec<String>> {
+    let mut lines = vec![];
+    for line in reader.lines() {
+        match line {
+            Ok(line) => lines.push(line),
+            Err(_) => return Err(StorageError::FileCorrupted.into()),
+        }
+    }
+    Ok(lines)
+}
+
+fn render_ndjson(tdo: &Tdo) -> String {
+    let mut out = String::new();
+    for list in &tdo.lists {
+        for todo in &list.todos {
+            out.push_str(&json!({
+                "list": list.name,
+                "id": todo.id,
+                "name": todo.name,
+                "done": todo.done,
+            }).to_string());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn parse_ndjson<R: BufRead>(reader: R) -> TdoResult<Vec<TodoList>> {
+    let mut lists: Vec<TodoList> = vec![];
+    for (number, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return Err(StorageError::FileCorrupted.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row: super::serde_json::Value = match super::serde_json::from_str(&line) {
+            Ok(row) => row,
+            Err(_) => return Err(StorageError::UnableToConvert(number + 1).into()),
+        };
+
+        let list_name = row.get("list").and_then(|v| v.as_str());
+        let id = row.get("id").and_then(|v| v.as_u64());
+        let name = row.get("name").and_then(|v| v.as_str());
+        let done = row.get("done").and_then(|v| v.as_bool());
+
+        match (list_name, id, name, done) {
+            (Some(list_name), Some(id), Some(name), Some(done)) => {
+                push_todo(&mut lists, list_name, id as u32, name, done);
+            }
+            _ => return Err(StorageError::UnableToConvert(number + 1).into()),
+        }
+    }
+    Ok(lists)
+}
+
+fn render_csv(tdo: &Tdo) -> String {
+    let mut out = String::from("list,id,name,done\n");
+    for list in &tdo.lists {
+        for todo in &list.todos {
+            out.push_str(&format!("{},{},{},{}\n",
+                                   csv_escape(&list.name),
+                                   todo.id,
+                                   csv_escape(&todo.name),
+                                   todo.done));
+        }
+    }
+    out
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains(',') || field.contains('"') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn parse_csv(lines: &[String]) -> TdoResult<Vec<TodoList>> {
+    let mut lists: Vec<TodoList> = vec![];
+    for (number, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() || line.starts_with("list,id,name,done") {
+            continue;
+        }
+
+        let fields = match parse_csv_line(line) {
+            Some(fields) if fields.len() == 4 => fields,
+            _ => return Err(StorageError::UnableToConvert(number + 1).into()),
+        };
+
+        let id: u32 = match fields[1].parse() {
+            Ok(id) => id,
+            Err(_) => return Err(StorageError::UnableToConvert(number + 1).into()),
+        };
+        let done: bool = match fields[3].parse() {
+            Ok(done) => done,
+            Err(_) => return Err(StorageError::UnableToConvert(number + 1).into()),
+        };
+
+        push_todo(&mut lists, &fields[0], id, &fields[2], done);
+    }
+    Ok(lists)
+}
+
+/// Splits a CSV row into its fields, undoing `csv_escape`: a field wrapped
+/// in `"..."` may itself contain commas, and `""` inside it is an escaped
+/// quote. Returns `None` on an unterminated quoted field.
+fn parse_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else {
+            match c {
+                '"' if field.is_empty() => in_quotes = true,
+                ',' => {
+                    fields.push(field);
+                    field = String::new();
+                }
+                c => field.push(c),
+            }
+        }
+    }
+
+    if in_quotes {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+fn render_markdown(tdo: &Tdo) -> String {
+    let mut out = String::new();
+    for list in &tdo.lists {
+        out.push_str(&format!("## {}\n", list.name));
+        for todo in &list.todos {
+            let box_char = if todo.done { "x" } else { " " };
+            out.push_str(&format!("- [{}] {}\n", box_char, todo.name));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Markdown carries no ids, so imported todos are numbered sequentially.
+fn parse_markdown(lines: &[String]) -> Vec<TodoList> {
+    let mut lists: Vec<TodoList> = vec![];
+    let mut current_list = String::from("default");
+    let mut next_id = 1;
+
+    for line in lines {
+        if let Some(heading) = line.trim().strip_prefix("## ") {
+            current_list = heading.to_string();
+        } else if let Some(rest) = line.trim().strip_prefix("- [ ] ") {
+            push_todo(&mut lists, &current_list, next_id, rest, false);
+            next_id += 1;
+        } else if let Some(rest) = line.trim().strip_prefix("- [x] ") {
+            push_todo(&mut lists, &current_list, next_id, rest, true);
+            next_id += 1;
+        }
+    }
+    lists
+}
+
+fn push_todo(lists: &mut Vec<TodoList>, list_name: &str, id: u32, name: &str, done: bool) {
+    let index = lists.iter().position(|l| l.name == list_name).unwrap_or_else(|| {
+        lists.push(TodoList::new(list_name));
+        lists.len() - 1
+    });
+
+    let mut todo = Todo::new(id, name);
+    if done {
+        todo.set_done();
+    }
+    lists[index].add(todo);
+}
@@ -0,0 +1,66 @@
+//! A single named list of todos.
+use todo::Todo;
+use error::*;
+
+/// A named collection of todos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoList {
+    /// The list's name, used to address it from `Tdo`.
+    pub name: String,
+    /// The todos it contains, in insertion order.
+    pub todos: Vec<Todo>,
+}
+
+impl TodoList {
+    /// Create a new, empty list with the given name.
+    pub fn new(name: &str) -> TodoList {
+        TodoList {
+            name: name.to_string(),
+            todos: vec![],
+        }
+    }
+
+    /// Append a todo to the end of the list.
+    pub fn add(&mut self, todo: Todo) {
+        self.todos.push(todo);
+    }
+
+    /// Reinsert a todo at a specific position, clamped to the list's
+    /// current length. Used by `Tdo::undo` to put removed todos back where
+    /// they were.
+    pub fn insert(&mut self, position: usize, todo: Todo) {
+        let position = position.min(self.todos.len());
+        self.todos.insert(position, todo);
+    }
+
+    /// Mark the todo with the given id as done.
+    pub fn done_id(&mut self, id: u32) -> TdoResult<()> {
+        match self.todos.iter_mut().find(|t| t.id == id) {
+            Some(todo) => {
+                todo.set_done();
+                Ok(())
+            }
+            None => Err(TodoError::NoSuchTodo.into()),
+        }
+    }
+
+    /// Remove and return the todo with the given id.
+    pub fn remove_id(&mut self, id: u32) -> TdoResult<Todo> {
+        match self.todos.iter().position(|t| t.id == id) {
+            Some(index) => Ok(self.todos.remove(index)),
+            None => Err(TodoError::NoSuchTodo.into()),
+        }
+    }
+
+    /// Remove every todo already marked as done.
+    pub fn clean(&mut self) {
+        self.todos.retain(|t| !t.done);
+    }
+}
+
+impl Default for TodoList {
+    /// The list named `default` that every `Tdo` container starts with.
+    fn default() -> TodoList {
+        TodoList::new("default")
+    }
+}
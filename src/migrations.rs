@@ -0,0 +1,121 @@
+//! Versioned migration pipeline for on-disk `Tdo` dumps.
+//!
+//! Every release that changes the on-disk layout registers one `Migration`
+//! step here. `migrate` reads the `version` field of a raw dump and walks
+//! forward, applying steps in order, until no further step applies. It does
+//! not require the result to match the running crate's exact version: a
+//! dump already on the current schema but stamped with a different patch
+//! version just finds no applicable step and passes through unchanged,
+//! leaving final validation to `serde_json::from_value`.
+use super::serde_json::Value;
+use error::*;
+
+/// A single schema migration step, owning the knowledge of exactly one
+/// version bump.
+pub struct Migration {
+    /// The `major.minor` schema this step expects its input to be in. Patch
+    /// versions don't change the on-disk layout, so matching is keyed on
+    /// schema rather than the dump's exact version string.
+    pub from: &'static str,
+    /// The full version this step's output is stamped with.
+    pub to: &'static str,
+    /// The actual transformation.
+    pub apply: fn(Value) -> TdoResult<Value>,
+}
+
+/// All known migrations, sorted by their `from` schema.
+fn migrations() -> Vec<Migration> {
+    vec![
+        Migration {
+            from: "0.1",
+            to: "0.2.0",
+            apply: migrate_0_1_to_0_2,
+        },
+    ]
+}
+
+/// The `major.minor` schema a dump's version string belongs to, e.g. `"0.1"`
+/// for `"0.1.3"`. The on-disk layout only changes on a minor bump, so this
+/// is what migration steps are keyed on rather than the exact patch string.
+fn schema_of(version: &str) -> String {
+    version.splitn(3, '.').take(2).collect::<Vec<_>>().join(".")
+}
+
+/// Migrates a raw JSON dump forward through every applicable schema bump.
+///
+/// The dump's own `version` field (or, if missing/unparseable, the oldest
+/// known schema) is used as the starting point. Migration stops, not at a
+/// specific target version, but as soon as no registered step's `from`
+/// matches the data's current schema — so a dump already on the latest
+/// known schema, even stamped with a never-seen patch version, passes
+/// through untouched instead of being rejected.
+pub fn migrate(mut data: Value) -> TdoResult<Value> {
+    let mut current = data.get("version")
+        .and_then(|v| v.as_str())
+        .filter(|v| schema_of(v).split('.').all(|part| part.parse::<u32>().is_ok()))
+        .unwrap_or("0.1.0")
+        .to_string();
+
+    while let Some(step) = migrations().into_iter().find(|m| m.from == schema_of(&current)) {
+        data = (step.apply)(data)?;
+        current = step.to.to_string();
+    }
+
+    Ok(data)
+}
+
+/// `0.1.x` dumped lists as a flat map `{ list_name: { id: [name, done] } }`.
+/// `0.2.x` wraps them as `{ lists: [ { name, todos: [...] } ], version }`.
+fn migrate_0_1_to_0_2(data: Value) -> TdoResult<Value> {
+    let map = match data.as_object() {
+        Some(map) => map,
+        None => return Err(StorageError::FileCorrupted.into()),
+    };
+
+    let mut lists = vec![];
+    for (list_name, todos) in map.iter() {
+        let todos_map = match todos.as_object() {
+            Some(m) => m,
+            None => return Err(StorageError::FileCorrupted.into()),
+        };
+
+        let mut todo_values = vec![];
+        for (id, fields) in todos_map.iter() {
+            // `UnableToConvert` carries a line number for the line-oriented
+            // import formats (see `format.rs`); a JSON-value migration has
+            // no line to name, so malformed legacy data is `FileCorrupted`
+            // here instead.
+            let id: u32 = match id.parse() {
+                Ok(id) => id,
+                Err(_) => return Err(StorageError::FileCorrupted.into()),
+            };
+            let fields = match fields.as_array() {
+                Some(fields) if fields.len() == 2 => fields,
+                _ => return Err(StorageError::FileCorrupted.into()),
+            };
+            let name = match fields[0].as_str() {
+                Some(name) => name,
+                None => return Err(StorageError::FileCorrupted.into()),
+            };
+            let done = match fields[1].as_bool() {
+                Some(done) => done,
+                None => return Err(StorageError::FileCorrupted.into()),
+            };
+            todo_values.push(json!({
+                "id": id,
+                "name": name,
+                "done": done,
+            }));
+        }
+
+        lists.push(json!({
+            "name": list_name,
+            "todos": todo_values,
+        }));
+    }
+
+    Ok(json!({
+        "lists": lists,
+        "version": "0.2.0",
+    }))
+}
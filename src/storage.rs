@@ -0,0 +1,71 @@
+//! Pluggable persistence backends for `Tdo`.
+//!
+//! `Tdo::load`/`save` are thin wrappers around a `Storage` implementation.
+//! `FileStorage` is the default backend and owns what used to be the JSON
+//! file handling directly on `Tdo`, migration pipeline included. Other
+//! backends (an in-memory store for tests, a future networked/sync store)
+//! only need to implement `read`/`write` and map their own failure modes
+//! onto `StorageError` via `From`.
+use std::fs::{self, File};
+use std::io::Write;
+use tdo::Tdo;
+use error::*;
+use migrations;
+
+/// A persistence backend for `Tdo` containers.
+pub trait Storage {
+    /// Load a `Tdo` from this backend.
+    fn read(&self) -> TdoResult<Tdo>;
+    /// Persist a `Tdo` to this backend.
+    fn write(&self, tdo: &Tdo) -> TdoResult<()>;
+}
+
+/// The default backend: a single JSON file on the local filesystem.
+///
+/// Reads run the file through the migration pipeline (see the `migrations`
+/// module); writes go through a temp-file-and-rename so an interrupted
+/// write can never corrupt an existing database.
+pub struct FileStorage {
+    path: String,
+}
+
+impl FileStorage {
+    /// Create a backend rooted at `path`.
+    pub fn new(path: &str) -> FileStorage {
+        FileStorage { path: path.to_string() }
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&self) -> TdoResult<Tdo> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(_) => return Err(StorageError::FileNotFound.into()),
+        };
+
+        let raw = match super::serde_json::from_reader(&file) {
+            Ok(raw) => raw,
+            Err(_) => return Err(StorageError::FileCorrupted.into()),
+        };
+
+        let migrated = migrations::migrate(raw)?;
+
+        match super::serde_json::from_value(migrated) {
+            Ok(tdo) => Ok(tdo),
+            Err(_) => Err(StorageError::FileCorrupted.into()),
+        }
+    }
+
+    fn write(&self, tdo: &Tdo) -> TdoResult<()> {
+        let tmp_path = format!("{}.tmp", self.path);
+
+        let mut file = File::create(&tmp_path).map_err(StorageError::SaveFailure)?;
+        super::serde_json::to_writer_pretty(&mut file, tdo)
+            .map_err(|e| StorageError::SaveFailure(e.into()))?;
+        file.flush().map_err(StorageError::SaveFailure)?;
+        file.sync_all().map_err(StorageError::SaveFailure)?;
+
+        fs::rename(&tmp_path, &self.path).map_err(StorageError::SaveFailure)?;
+        Ok(())
+    }
+}
@@ -1,10 +1,32 @@
 //! General implementation of tdos base structure.
-use json::parse;
-use std::fs::File;
-use std::io::Read;
 use list::TodoList;
 use todo::Todo;
 use error::*;
+use storage::{FileStorage, Storage};
+
+/// How many destructive operations are remembered for `undo()` by default.
+const DEFAULT_UNDO_DEPTH: usize = 10;
+
+/// A single destructive operation remembered by the undo register, holding
+/// enough information to put the removed data back where it was.
+#[derive(Debug, Clone)]
+enum UndoEntry {
+    /// A todo removed by `remove_id`, at its original list and position.
+    RemovedTodo {
+        list_index: usize,
+        position: usize,
+        todo: Todo,
+    },
+    /// A list removed by `remove_list`, at its original position.
+    RemovedList { index: usize, list: TodoList },
+    /// The todos removed by a single `clean_lists` call, each at its
+    /// original list and position.
+    Cleaned(Vec<(usize, usize, Todo)>),
+}
+
+fn default_undo_depth() -> usize {
+    DEFAULT_UNDO_DEPTH
+}
 
 /// Basic container structure for a set of todo lists.
 ///
@@ -19,6 +41,13 @@ pub struct Tdo {
     pub lists: Vec<TodoList>,
     /// The tdo version the last dump was saved with.
     version: String,
+    /// Destructive operations that `undo()` can still reverse, oldest first.
+    /// Not persisted: a fresh load starts with nothing to undo.
+    #[serde(skip, default)]
+    undo_stack: Vec<UndoEntry>,
+    /// How many destructive operations `undo_stack` remembers at once.
+    #[serde(skip, default = "default_undo_depth")]
+    undo_depth: usize,
 }
 
 impl Tdo {
@@ -35,13 +64,30 @@ impl Tdo {
         Tdo {
             lists: vec![TodoList::default()],
             version: env!("CARGO_PKG_VERSION").to_string(),
+            undo_stack: vec![],
+            undo_depth: DEFAULT_UNDO_DEPTH,
+        }
+    }
+
+    /// Build a `Tdo` container from an already-assembled set of lists,
+    /// stamped with the current crate version.
+    ///
+    /// Used by the format importers to hand back a `Tdo` without reaching
+    /// into its private `version` field.
+    pub fn from_lists(lists: Vec<TodoList>) -> Tdo {
+        Tdo {
+            lists: lists,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            undo_stack: vec![],
+            undo_depth: DEFAULT_UNDO_DEPTH,
         }
     }
 
     /// Load a saved `Tdo` container from a JSON file.
     ///
-    /// This function returns a `ResultType` which will yield the
-    /// deserialized JSON or a `serde_json::Error`.
+    /// Thin convenience wrapper around `FileStorage::read`; see the
+    /// `storage` module for the actual file handling and migration
+    /// pipeline, and use `Storage` directly for other backends.
     ///
     /// # Example
     ///
@@ -50,22 +96,14 @@ impl Tdo {
     /// let mut tdo = Tdo::load("foo.json");
     /// ```
     pub fn load(path: &str) -> TdoResult<Tdo> {
-        match File::open(path) {
-            Ok(file) => {
-                match super::serde_json::from_reader(&file) {
-                    Ok(tdo) => Ok(tdo),
-                    Err(_) => update_json(path),
-                }
-            }
-            Err(_) => Err(StorageError::FileNotFound.into()),
-        }
-
+        FileStorage::new(path).read()
     }
 
     /// Dump the `Tdo` container to a JSON file.
     ///
-    /// This function returns a `ResultType` yielding a `StorageError::SaveFailure`
-    /// if the JSON file could not be opened/saved.
+    /// Thin convenience wrapper around `FileStorage::write`; see the
+    /// `storage` module for the atomic temp-file-and-rename behavior, and
+    /// use `Storage` directly for other backends.
     ///
     /// # Example
     ///
@@ -76,16 +114,7 @@ impl Tdo {
     /// assert_eq!(res.unwrap(), ());
     /// ```
     pub fn save(&self, path: &str) -> TdoResult<()> {
-        // TODO: At this point we could be much more precise about the error if we would include
-        // the error from the file system as SaveFailure(ArbitraryErrorFromFS)
-        //  -- Feliix42 (2017-03-14; 17:04)
-        match File::create(path) {
-            Ok(mut f) => {
-                let _ = super::serde_json::to_writer_pretty(&mut f, self);
-                Ok(())
-            }
-            Err(_) => Err(StorageError::SaveFailure.into()),
-        }
+        FileStorage::new(path).write(self)
     }
 
     /// Add a todo list to the container.
@@ -106,7 +135,11 @@ impl Tdo {
         } else {
             match self.get_list_index(list_name) {
                 Ok(index) => {
-                    self.lists.remove(index);
+                    let list = self.lists.remove(index);
+                    self.record_undo(UndoEntry::RemovedList {
+                        index: index,
+                        list: list,
+                    });
                     Ok(())
                 }
                 Err(_) => Err(TodoError::NoSuchList.into()),
@@ -138,19 +171,84 @@ impl Tdo {
     }
 
     /// Cycle through all todo lists and remove a todo with the given id.
-    /// This function has no return value and thus won't indicate whether
-    /// there was a matching todo found.
-    pub fn remove_id(&mut self, id: u32) {
-        for mut list in self.to_owned().lists.into_iter() {
-            let _ = list.remove_id(id);
+    /// Returns whether a matching todo was found and removed.
+    pub fn remove_id(&mut self, id: u32) -> bool {
+        for list_index in 0..self.lists.len() {
+            let position = self.lists[list_index].todos.iter().position(|t| t.id == id);
+            if let Some(position) = position {
+                if let Ok(todo) = self.lists[list_index].remove_id(id) {
+                    self.record_undo(UndoEntry::RemovedTodo {
+                        list_index: list_index,
+                        position: position,
+                        todo: todo,
+                    });
+                    return true;
+                }
+            }
         }
+        false
     }
 
     /// Remove all todos that have been marked as _done_ from all todo lists.
     pub fn clean_lists(&mut self) {
-        for list in 0..self.lists.len() {
-            self.lists[list].clean();
+        let mut removed = vec![];
+        for list_index in 0..self.lists.len() {
+            for (position, todo) in self.lists[list_index].todos.iter().enumerate() {
+                if todo.done {
+                    removed.push((list_index, position, todo.clone()));
+                }
+            }
+            self.lists[list_index].clean();
         }
+        if !removed.is_empty() {
+            self.record_undo(UndoEntry::Cleaned(removed));
+        }
+    }
+
+    /// Whether there is a destructive operation that `undo()` could reverse.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// How many destructive operations the undo register remembers at once.
+    pub fn undo_depth(&self) -> usize {
+        self.undo_depth
+    }
+
+    /// Configure how many destructive operations the undo register
+    /// remembers. Shrinking the depth immediately drops the oldest entries.
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        self.undo_depth = depth;
+        let overflow = self.undo_stack.len().saturating_sub(depth);
+        self.undo_stack.drain(0..overflow);
+    }
+
+    /// Reverse the last `remove_id`, `remove_list` or `clean_lists` call, if
+    /// any, reinserting the removed data at its original position.
+    pub fn undo(&mut self) -> TdoResult<()> {
+        match self.undo_stack.pop() {
+            Some(UndoEntry::RemovedTodo { list_index, position, todo }) => {
+                self.lists[list_index].insert(position, todo);
+                Ok(())
+            }
+            Some(UndoEntry::RemovedList { index, list }) => {
+                self.lists.insert(index, list);
+                Ok(())
+            }
+            Some(UndoEntry::Cleaned(removed)) => {
+                for (list_index, position, todo) in removed {
+                    self.lists[list_index].insert(position, todo);
+                }
+                Ok(())
+            }
+            None => Err(TodoError::NothingToUndo.into()),
+        }
+    }
+
+    fn record_undo(&mut self, entry: UndoEntry) {
+        self.undo_stack.push(entry);
+        let overflow = self.undo_stack.len().saturating_sub(self.undo_depth);
+        self.undo_stack.drain(0..overflow);
     }
 
     fn get_list_index(&self, name: &str) -> TdoResult<usize> {
@@ -162,44 +260,3 @@ impl Tdo {
         }
     }
 }
-
-fn update_json(path: &str) -> TdoResult<Tdo> {
-    let mut file = File::open(path).unwrap();
-    let mut data = String::new();
-    file.read_to_string(&mut data).unwrap();
-    let mut json = match parse(&data) {
-        Ok(content) => content,
-        Err(_) => return Err(StorageError::FileCorrupted.into()),
-    };
-
-    let mut lists: Vec<TodoList> = vec![];
-
-    for outer in json.entries_mut() {
-        let mut list = TodoList::new(outer.0);
-        for inner in outer.1.entries_mut() {
-            let tdo_id = match inner.0.parse::<u32>() {
-                Ok(id) => id,
-                Err(_) => return Err(StorageError::UnableToConvert.into()),
-            };
-            let done = match inner.1.pop().as_bool() {
-                Some(x) => x,
-                None => return Err(StorageError::UnableToConvert.into()),
-            };
-            let tdo_name = match inner.1.pop().as_str() {
-                Some(x) => String::from(x),
-                None => return Err(StorageError::UnableToConvert.into()),
-            };
-            let mut todo = Todo::new(tdo_id, &tdo_name);
-            if done {
-                todo.set_done();
-            }
-            list.add(todo);
-        }
-        lists.push(list);
-    }
-    let tdo = Tdo {
-        lists: lists,
-        version: env!("CARGO_PKG_VERSION").to_string(),
-    };
-    Ok(tdo)
-}